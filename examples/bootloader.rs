@@ -6,6 +6,10 @@ use rust_simple_init::services::mount;
 use rust_simple_init::services::console::ConsoleService;
 use rust_simple_init::services::dev::DeviceManagerService;
 use rust_simple_init::services::bootloader::Bootloader;
+use rust_simple_init::services::bootloader::BlockDeviceType;
+use rust_simple_init::services::bootloader::BootSelector;
+use rust_simple_init::services::serial_boot::SerialBoot;
+use rust_simple_init::services::serial_boot::SerialBootHandle;
 use rust_simple_init::logging::Logger;
 
 pub fn main() -> std::result::Result<(), Box<dyn std::error::Error>>
@@ -50,14 +54,31 @@ pub fn main() -> std::result::Result<(), Box<dyn std::error::Error>>
 	// start device manager
 	manager.add_service(&mut rt, DeviceManagerService::new(), true);
 
+	// Field-recovery fallback: lets ttyS0 keep serving its login shell until
+	// Bootloader finds nothing bootable, at which point it arms and streams
+	// a kernel image in over XMODEM instead. Created before ConsoleService so
+	// it can be handed the same instance, which relinquishes the tty's
+	// descriptor (instead of SerialBoot opening a second, competing one)
+	// once it observes the handle armed.
+	let serial_recovery = SerialBootHandle::new();
+
 	// add serial consoles
 	// manager.add_service(&mut rt, ConsoleService::new("ttyACM0", 115200, true), true);
 	// manager.add_service(&mut rt, ConsoleService::new("ttyAMA0", 115200, true), true);
 	// manager.add_service(&mut rt, ConsoleService::new("ttyUSB0", 115200, true), true);
-	manager.add_service(&mut rt, ConsoleService::new("ttyS0", 115200, true), true);
+	manager.add_service(&mut rt, ConsoleService::new("ttyS0", 115200, true).with_serial_recovery(serial_recovery.clone()), true);
+
+	// Start the boot loading service that discovers boot sources, preferring
+	// internal storage, then USB, then network, before any other device
+	let order = vec![
+		BootSelector::DeviceType(BlockDeviceType::Internal),
+		BootSelector::DeviceType(BlockDeviceType::USB),
+		BootSelector::DeviceType(BlockDeviceType::Network),
+		BootSelector::DeviceType(BlockDeviceType::Other),
+	];
+	manager.add_service(&mut rt, Bootloader::new(order).with_serial_recovery(serial_recovery.clone()), true);
 
-	// Start the boot loading service that discovers boot sources
-	manager.add_service(&mut rt, Bootloader::new(), true);
+	manager.add_service(&mut rt, SerialBoot::new("ttyS0", serial_recovery), true);
 
 	return rt.poll(&mut manager, false);
 }
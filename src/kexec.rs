@@ -0,0 +1,37 @@
+use std::ffi::CString;
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+
+use crate::Result;
+
+pub const KEXEC_FILE_UNLOAD : u64 = 0x00000001;
+pub const KEXEC_FILE_ON_CRASH : u64 = 0x00000002;
+/// Don't pass an initramfs to kexec_file_load() — see the kexec_file_load(2) man page.
+pub const KEXEC_FILE_NO_INITRAMFS : u64 = 0x00000004;
+
+/// Load a kernel (and optional initrd) for the next reboot via the
+/// `kexec_file_load(2)` syscall. Does not itself trigger the reboot, see
+/// `reboot_to_kexec`.
+pub fn load(kernel : &File, initrd : Option<&File>, cmdline : &str, flags : u64) -> Result<()> {
+	let cmdline = CString::new(cmdline)?;
+	let kernel_fd = kernel.as_raw_fd();
+	let initrd_fd = initrd.map(|f| f.as_raw_fd()).unwrap_or(-1);
+	let flags = flags | if initrd.is_none() { KEXEC_FILE_NO_INITRAMFS } else { 0 };
+
+	let ret = unsafe {
+		libc::syscall(libc::SYS_kexec_file_load, kernel_fd, initrd_fd,
+			cmdline.as_bytes_with_nul().len(), cmdline.as_ptr(), flags)
+	};
+
+	if ret != 0 {
+		return Err(format!("kexec_file_load failed: {}", io::Error::last_os_error()).into());
+	}
+	return Ok(());
+}
+
+/// Jump into the kernel loaded by a prior successful `load()` call.
+pub fn reboot_to_kexec() -> Result<()> {
+	nix::sys::reboot::reboot(nix::sys::reboot::RebootMode::RB_KEXEC)?;
+	return Ok(());
+}
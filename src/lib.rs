@@ -1,7 +1,9 @@
 pub mod services;
 pub mod procfs;
 pub mod sysfs;
+pub mod superblock;
 pub mod kmod;
+pub mod kexec;
 pub mod configfs;
 pub mod uevent;
 pub mod runtime;
@@ -0,0 +1,101 @@
+use std::cell::OnceCell;
+use std::os::linux::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+pub type DevT = libc::dev_t;
+
+#[derive(Debug, Clone)]
+pub struct MountEntry
+{
+	pub mount_id : u32,
+	pub parent_id : u32,
+	pub dev : DevT,
+	pub root : PathBuf,
+	pub mount_point : PathBuf,
+	pub options : String,
+	pub fstype : String,
+	pub source : String,
+	pub super_options : String,
+}
+
+// Parse a single "mountinfo" line, see proc(5):
+//   36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw,errors=continue
+// Optional tagged fields (position 7+) are terminated by a lone "-".
+fn parse_mountinfo_line(line : &str) -> Option<MountEntry> {
+	let (fields, rest) = line.split_once(" - ")?;
+	let fields : Vec<&str> = fields.split_whitespace().collect();
+	if fields.len() < 6 {
+		return None;
+	}
+
+	let (major, minor) = fields[2].split_once(':')?;
+
+	let rest : Vec<&str> = rest.split_whitespace().collect();
+	if rest.len() < 3 {
+		return None;
+	}
+
+	return Some(MountEntry {
+		mount_id : fields[0].parse().ok()?,
+		parent_id : fields[1].parse().ok()?,
+		dev : nix::sys::stat::makedev(major.parse().ok()?, minor.parse().ok()?),
+		root : PathBuf::from(fields[3]),
+		mount_point : PathBuf::from(fields[4]),
+		options : fields[5].to_owned(),
+		fstype : rest[0].to_owned(),
+		source : rest[1].to_owned(),
+		super_options : rest[2].to_owned(),
+	});
+}
+
+fn read_mountinfo() -> Vec<MountEntry> {
+	match std::fs::read_to_string("/proc/self/mountinfo") {
+		Ok(content) => content.lines().filter_map(parse_mountinfo_line).collect(),
+		Err(_) => Vec::new(),
+	}
+}
+
+/// A cached view of `/proc/self/mountinfo`, parsed once on first access (like
+/// proxmox's DiskManage `OnceCell`) so repeated "is this mounted" checks don't
+/// re-read `/proc/self/mountinfo`. Call `refresh` whenever a mount
+/// `ServiceEvent` fires to pick up the change.
+///
+/// Lookups by `dev_t` are a linear search of the cached entries; the table is
+/// not indexed by `dev_t` or by mount point.
+pub struct MountInfo
+{
+	entries : OnceCell<Vec<MountEntry>>,
+}
+
+impl MountInfo
+{
+	pub fn new() -> Self {
+		return Self {
+			entries : OnceCell::new(),
+		};
+	}
+
+	fn entries(&self) -> &[MountEntry] {
+		self.entries.get_or_init(read_mountinfo)
+	}
+
+	pub fn is_mounted(&self, dev : DevT) -> bool {
+		self.entries().iter().any(|e| e.dev == dev)
+	}
+
+	pub fn mount_point_for(&self, dev : DevT) -> Option<PathBuf> {
+		self.entries().iter().find(|e| e.dev == dev).map(|e| e.mount_point.clone())
+	}
+
+	pub fn refresh(&mut self) {
+		self.entries = OnceCell::new();
+	}
+}
+
+/// Whether the device node at `device` is currently mounted anywhere.
+pub fn device_mounted<P: AsRef<Path>>(device : P) -> bool {
+	if let Ok(meta) = device.as_ref().metadata() {
+		return MountInfo::new().is_mounted(meta.st_rdev());
+	}
+	return false;
+}
@@ -1,19 +1,50 @@
+use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
 use std::os::linux::fs::MetadataExt;
 use std::process::Command;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use super::super::*;
+use super::serial_boot;
 use service::{Service, ServiceEvent, ServiceState};
 use runtime::Runtime;
 use procfs;
+use kexec;
+use superblock;
 use crate::Result;
 
 #[derive(Debug, Clone)]
-struct BootEntry
+pub(crate) struct BootEntry
 {
+	id : String,
 	kernel : PathBuf,
-	initramfs : Option<PathBuf>,
+	initramfs : Vec<PathBuf>,
 	append : Option<String>,
+	title : Option<String>,
+	version : Option<String>,
+	sort_key : Option<String>,
+	machine_id : Option<String>,
+	architecture : Option<String>,
+	// Set false when signature verification is configured and fails; such
+	// entries are kept around for display/logging but select_boot_entry skips them.
+	verified : bool,
+}
+
+// Build a standalone BootEntry not backed by a probed partition, e.g. an image
+// received over SerialBoot and staged on the /var/volatile tmpfs.
+pub(crate) fn recovery_entry(id : &str, kernel : PathBuf, cmdline : Option<String>) -> BootEntry {
+	return BootEntry {
+		id : id.to_owned(),
+		kernel : kernel,
+		initramfs : Vec::new(),
+		append : cmdline,
+		title : Some("Serial recovery image".to_owned()),
+		version : None,
+		sort_key : None,
+		machine_id : None,
+		architecture : None,
+		verified : true,
+	};
 }
 
 enum BlockState
@@ -33,7 +64,7 @@ pub enum BlockDeviceType
 	Network,
 }
 
-struct DeviceProbe
+pub struct DeviceProbe
 {
 	name : String,
 	device : PathBuf,
@@ -41,6 +72,374 @@ struct DeviceProbe
 	point : PathBuf,
 	state : BlockState,
 	entries : Vec<BootEntry>,
+	matched : Option<usize>,
+	fstype : Option<superblock::FsType>,
+	uuid : Option<String>,
+	label : Option<String>,
+}
+
+impl DeviceProbe
+{
+	pub fn name(&self) -> &str { &self.name }
+	pub fn point(&self) -> &Path { &self.point }
+	pub fn devicetype(&self) -> &BlockDeviceType { &self.devicetype }
+	pub fn fstype(&self) -> Option<&superblock::FsType> { self.fstype.as_ref() }
+	pub fn uuid(&self) -> Option<&str> { self.uuid.as_deref() }
+	pub fn label(&self) -> Option<&str> { self.label.as_deref() }
+}
+
+/// Selects which candidate to boot, tried in the order given. Device-type
+/// priority is kept for the simple case; `Uuid`/`Label` let `main` pin a
+/// specific root partition, e.g. matching a `root=UUID=...` kernel argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BootSelector
+{
+	DeviceType(BlockDeviceType),
+	Uuid(String),
+	Label(String),
+}
+
+/// A pluggable partition layout recognizer, tried in order against every
+/// successfully mounted device until one matches. Modeled on the fshost
+/// matcher pattern: `matches` is a cheap pre-check, `process` does the actual
+/// (possibly fallible) work of turning the mounted partition into entries.
+pub trait Matcher
+{
+	fn matches(&self, probe : &DeviceProbe) -> bool;
+	fn process(&mut self, probe : &mut DeviceProbe, runtime : &mut Runtime) -> Result<Vec<BootEntry>>;
+}
+
+fn find_case_insensitive(dir : &Path, name : &str) -> Option<String> {
+	let name = name.to_lowercase();
+	if let Ok(entries) = dir.read_dir() {
+		for entry in entries.flatten() {
+			if entry.path().is_dir() {
+				continue;
+			}
+			if let Some(filename) = entry.file_name().to_str() {
+				if filename.to_lowercase() == name {
+					return Some(filename.to_owned());
+				}
+			}
+		}
+	}
+	return None;
+}
+
+/// Removable-media EFI layout, e.g. `EFI/BOOT/BOOTX64.EFI`.
+pub struct EfiRemovableMatcher;
+
+impl Matcher for EfiRemovableMatcher
+{
+	fn matches(&self, probe : &DeviceProbe) -> bool {
+		find_case_insensitive(&probe.point.join("EFI/BOOT"), "bootx64.efi").is_some()
+	}
+
+	fn process(&mut self, probe : &mut DeviceProbe, runtime : &mut Runtime) -> Result<Vec<BootEntry>> {
+		let subdir = PathBuf::from("EFI/BOOT");
+		let filename = find_case_insensitive(&probe.point.join(&subdir), "bootx64.efi").ok_or("EFI boot file missing")?;
+		let subpath = subdir.join(filename);
+
+		runtime.logger.service_log("bootloader", &format!("EFI matcher found bootable at {:?}", probe.point.join(&subpath)));
+		return Ok(vec![BootEntry {
+			id : "efi".to_owned(),
+			kernel : subpath,
+			initramfs : Vec::new(),
+			append : None,
+			title : None,
+			version : None,
+			sort_key : None,
+			machine_id : None,
+			architecture : None,
+			verified : true,
+		}]);
+	}
+}
+
+// Parse a single Boot Loader Specification Type #1 entry file, per
+// https://uapi-group.org/specifications/specs/boot_loader_specification/
+fn parse_bls_entry(id : &str, path : &Path) -> Option<BootEntry> {
+	let content = std::fs::read_to_string(path).ok()?;
+
+	let mut kernel = None;
+	let mut initramfs = Vec::new();
+	let mut options : Vec<String> = Vec::new();
+	let mut title = None;
+	let mut version = None;
+	let mut sort_key = None;
+	let mut machine_id = None;
+	let mut architecture = None;
+
+	for line in content.lines() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+
+		let (key, value) = match line.split_once(char::is_whitespace) {
+			Some((key, value)) => (key, value.trim()),
+			None => (line, ""),
+		};
+
+		match key {
+			"linux" => kernel = Some(PathBuf::from(value.trim_start_matches('/'))),
+			"initrd" => initramfs.push(PathBuf::from(value.trim_start_matches('/'))),
+			"options" => options.push(value.to_owned()),
+			"title" => title = Some(value.to_owned()),
+			"version" => version = Some(value.to_owned()),
+			"sort-key" => sort_key = Some(value.to_owned()),
+			"machine-id" => machine_id = Some(value.to_owned()),
+			"architecture" => architecture = Some(value.to_owned()),
+			_ => {},
+		}
+	}
+
+	return Some(BootEntry {
+		id : id.to_owned(),
+		kernel : kernel?,
+		initramfs : initramfs,
+		append : if options.is_empty() { None } else { Some(options.join(" ")) },
+		title : title,
+		version : version,
+		sort_key : sort_key,
+		machine_id : machine_id,
+		architecture : architecture,
+		verified : true,
+	});
+}
+
+// Scan `loader/entries/*.conf` for BLS drop-ins, sorted by `sort-key` then
+// descending `version` so the newest entry is first. Returns None when the
+// directory does not exist so callers can fall back to other matchers.
+fn scan_bls_entries(point : &Path) -> Option<Vec<BootEntry>> {
+	let dir = point.join("loader/entries");
+	let entries = dir.read_dir().ok()?;
+
+	let mut found = Vec::new();
+	for entry in entries.flatten() {
+		let path = entry.path();
+		if path.extension().and_then(|e| e.to_str()) != Some("conf") {
+			continue;
+		}
+
+		let id = match path.file_stem().and_then(|s| s.to_str()) {
+			Some(id) => id.to_owned(),
+			None => continue,
+		};
+
+		if let Some(entry) = parse_bls_entry(&id, &path) {
+			found.push(entry);
+		}
+	}
+
+	// Compare versions piece by piece, numeric pieces as numbers, so that
+	// "5.10" sorts as newer than "5.9".
+	let version_key = |v : &Option<String>| -> Vec<(Option<u64>, String)> {
+		v.as_deref().unwrap_or("")
+			.split(|c : char| !c.is_ascii_alphanumeric())
+			.map(|p| (p.parse::<u64>().ok(), p.to_owned()))
+			.collect()
+	};
+	found.sort_by(|a, b| {
+		a.sort_key.cmp(&b.sort_key).then_with(|| version_key(&b.version).cmp(&version_key(&a.version)))
+	});
+
+	return Some(found);
+}
+
+/// Boot Loader Specification drop-in entries under `loader/entries/*.conf`.
+pub struct BlsMatcher;
+
+impl Matcher for BlsMatcher
+{
+	fn matches(&self, probe : &DeviceProbe) -> bool {
+		probe.point.join("loader/entries").is_dir()
+	}
+
+	fn process(&mut self, probe : &mut DeviceProbe, runtime : &mut Runtime) -> Result<Vec<BootEntry>> {
+		let entries = scan_bls_entries(&probe.point).ok_or("no loader/entries directory")?;
+		if entries.is_empty() {
+			return Err("no usable BLS entries found".into());
+		}
+
+		runtime.logger.service_log("bootloader", &format!("BLS matcher found {} entries for {}", entries.len(), probe.name));
+		return Ok(entries);
+	}
+}
+
+/// A bare `vmlinuz` (+ optional `initrd`) pair dropped at the partition root.
+pub struct RawKernelMatcher;
+
+impl Matcher for RawKernelMatcher
+{
+	fn matches(&self, probe : &DeviceProbe) -> bool {
+		probe.point.join("vmlinuz").is_file()
+	}
+
+	fn process(&mut self, probe : &mut DeviceProbe, runtime : &mut Runtime) -> Result<Vec<BootEntry>> {
+		let initramfs = if probe.point.join("initrd").is_file() {
+			vec![PathBuf::from("initrd")]
+		} else {
+			Vec::new()
+		};
+
+		runtime.logger.service_log("bootloader", &format!("raw kernel matcher found vmlinuz for {}", probe.name));
+		return Ok(vec![BootEntry {
+			id : "raw".to_owned(),
+			kernel : PathBuf::from("vmlinuz"),
+			initramfs : initramfs,
+			append : None,
+			title : None,
+			version : None,
+			sort_key : None,
+			machine_id : None,
+			architecture : None,
+			verified : true,
+		}]);
+	}
+}
+
+// Header written ahead of a kernel image (or in a detached "<kernel>.sig"
+// file alongside it) by the release signing tooling:
+//
+//   magic(8) channel(32) version(32) signature(64)
+//
+// `channel`/`version` are NUL-padded ASCII, kept only for logging; the
+// signature covers every byte of the kernel image that follows the header
+// (or, for a detached signature, the whole kernel file), followed by the
+// bytes of each of the entry's initrd files in order.
+const IMAGE_HEADER_MAGIC : &[u8; 8] = b"RSIMGv1\0";
+const IMAGE_HEADER_CHANNEL_LEN : usize = 32;
+const IMAGE_HEADER_VERSION_LEN : usize = 32;
+const IMAGE_HEADER_SIGNATURE_LEN : usize = 64;
+const IMAGE_HEADER_LEN : usize = 8 + IMAGE_HEADER_CHANNEL_LEN + IMAGE_HEADER_VERSION_LEN + IMAGE_HEADER_SIGNATURE_LEN;
+
+struct ImageHeader
+{
+	channel : String,
+	version : String,
+	signature : [u8; IMAGE_HEADER_SIGNATURE_LEN],
+}
+
+impl ImageHeader
+{
+	fn parse(bytes : &[u8]) -> Option<Self> {
+		if bytes.len() < IMAGE_HEADER_LEN || &bytes[0..8] != IMAGE_HEADER_MAGIC {
+			return None;
+		}
+
+		let channel_end = 8 + IMAGE_HEADER_CHANNEL_LEN;
+		let version_end = channel_end + IMAGE_HEADER_VERSION_LEN;
+		let signature_end = version_end + IMAGE_HEADER_SIGNATURE_LEN;
+
+		let mut signature = [0u8; IMAGE_HEADER_SIGNATURE_LEN];
+		signature.copy_from_slice(&bytes[version_end..signature_end]);
+
+		return Some(Self {
+			channel : trim_nul(&bytes[8..channel_end]),
+			version : trim_nul(&bytes[channel_end..version_end]),
+			signature : signature,
+		});
+	}
+
+	// Look for the header embedded at the start of `kernel_path`, falling back
+	// to a detached "<kernel>.sig" file alongside it. Returns the header and
+	// the offset into `kernel_path` the signed payload starts at (0 when the
+	// signature is detached, since the whole kernel file is then signed).
+	fn read_for(kernel_path : &Path) -> Option<(Self, u64)> {
+		if let Ok(mut file) = std::fs::File::open(kernel_path) {
+			let mut buf = [0u8; IMAGE_HEADER_LEN];
+			if file.read_exact(&mut buf).is_ok() {
+				if let Some(header) = Self::parse(&buf) {
+					return Some((header, IMAGE_HEADER_LEN as u64));
+				}
+			}
+		}
+
+		let mut sig_path = kernel_path.as_os_str().to_owned();
+		sig_path.push(".sig");
+		if let Ok(buf) = std::fs::read(&sig_path) {
+			if let Some(header) = Self::parse(&buf) {
+				return Some((header, 0));
+			}
+		}
+
+		return None;
+	}
+}
+
+fn trim_nul(bytes : &[u8]) -> String {
+	let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+	return String::from_utf8_lossy(&bytes[..end]).into_owned();
+}
+
+/// The ed25519 public key boot images are verified against. `None` disables
+/// verification entirely, preserving the historical unsigned-boot behaviour.
+/// Set to bake a key into the init binary at build time.
+const EMBEDDED_PUBLIC_KEY : Option<[u8; 32]> = None;
+
+/// Fallback path for a raw 32-byte ed25519 public key, for boards that
+/// provision it via configfs/sysfs rather than baking it into the binary.
+const PUBLIC_KEY_PATH : &str = "/sys/firmware/rust-simple-init/verify-key";
+
+fn trusted_key() -> Option<[u8; 32]> {
+	if let Some(key) = EMBEDDED_PUBLIC_KEY {
+		return Some(key);
+	}
+	return std::fs::read(PUBLIC_KEY_PATH).ok()?.try_into().ok();
+}
+
+// Check `entry`'s kernel+initrd bytes against `key`, returning the header on
+// a valid signature. Ok(None) means no (valid) signature was found at all,
+// distinct from Err which is an I/O failure or an unusable key. Covering the initrd as well as
+// the kernel stops a validly-signed kernel being paired with a swapped-in
+// malicious initrd.
+fn verify_entry(point : &Path, entry : &BootEntry, key : &[u8; 32]) -> Result<Option<ImageHeader>> {
+	let kernel_path = point.join(&entry.kernel);
+	let (header, offset) = match ImageHeader::read_for(&kernel_path) {
+		Some(found) => found,
+		None => return Ok(None),
+	};
+
+	let kernel = std::fs::read(&kernel_path)?;
+	let mut payload = kernel.get(offset as usize..).ok_or("signed image shorter than its own header")?.to_vec();
+	for initrd in &entry.initramfs {
+		payload.extend(std::fs::read(point.join(initrd))?);
+	}
+
+	let verifying_key = VerifyingKey::from_bytes(key)?;
+	let signature = Signature::from_bytes(&header.signature);
+
+	if verifying_key.verify_strict(&payload, &signature).is_err() {
+		return Ok(None);
+	}
+	return Ok(Some(header));
+}
+
+// Reject any entry that fails signature verification so select_boot_entry
+// skips it; entries are kept around (rather than dropped) so they still show
+// up in logs. A no-op when no trusted key is configured.
+fn verify_entries(point : &Path, entries : &mut [BootEntry], runtime : &mut Runtime) {
+	let key = match trusted_key() {
+		Some(key) => key,
+		None => return,
+	};
+
+	for entry in entries.iter_mut() {
+		match verify_entry(point, entry, &key) {
+			Ok(Some(header)) => {
+				runtime.logger.service_log("bootloader", &format!("entry {:?} signature verified (channel={:?}, version={:?})", entry.id, header.channel, header.version));
+			},
+			Ok(None) => {
+				runtime.logger.service_log("bootloader", &format!("rejecting entry {:?}: no valid signature found", entry.id));
+				entry.verified = false;
+			},
+			Err(e) => {
+				runtime.logger.service_log("bootloader", &format!("rejecting entry {:?}: signature check failed: {:?}", entry.id, e));
+				entry.verified = false;
+			},
+		}
+	}
 }
 
 pub fn file_is_chardev<P: AsRef<std::path::Path>>(path : P, major : u64, minor : u64) -> bool {
@@ -183,24 +582,41 @@ impl DeviceProbe
 			point : point,
 			state : BlockState::Unchecked,
 			entries : Vec::new(),
+			matched : None,
+			fstype : None,
+			uuid : None,
+			label : None,
 		});
 	}
 
-	fn mount(&mut self, runtime : &mut Runtime) -> crate::Result<()> {
+	fn mount(&mut self, runtime : &mut Runtime, mounts : &procfs::MountInfo) -> crate::Result<()> {
 		// Check if already mounted
-		if procfs::device_mounted(&self.device) {
-			return Err("Device already mounted".into());
+		if let Ok(meta) = self.device.metadata() {
+			if mounts.is_mounted(meta.st_rdev()) {
+				return Err("Device already mounted".into());
+			}
 		}
 
 		// Check/make the mount point
 		std::fs::create_dir_all(&self.point)?;
 
+		if let Some(info) = superblock::probe(&self.device) {
+			runtime.logger.service_log("bootloader", &format!("detected {:?} on {:?} (uuid={:?}, label={:?})", info.fstype, self.device, info.uuid, info.label));
+			self.uuid = info.uuid;
+			self.label = info.label;
+			self.fstype = Some(info.fstype);
+		}
+
 		let mut command = Command::new("mount");
 		command
 			.arg(&self.device)
 			.arg(&self.point)
 			.arg("-o").arg("ro");
 
+		if let Some(fstype) = &self.fstype {
+			command.arg("-t").arg(fstype.mount_type());
+		}
+
 		if let Ok(child) = command.spawn() {
 			runtime.logger.service_log("bootloader", &format!("mounting {:?} at {:?}", self.device, self.point));
 			self.state = BlockState::Mounting(child);
@@ -212,40 +628,6 @@ impl DeviceProbe
 		}
 	}
 
-	fn scan(&mut self, runtime : &mut Runtime) {
-		// search for the EFI boot files
-		let default = "bootx64.efi".to_lowercase();
-		let subdir = PathBuf::from("EFI");
-
-		if let Ok(entries) = self.point.join(&subdir).read_dir() {
-			for i in entries {
-				if let Ok(entry) = i {
-					if entry.path().is_dir() {
-						continue;
-					}
-					let filename = entry.file_name();
-
-					if let Some(filename) = filename.to_str() {
-						if filename.to_lowercase() == default {
-							let subpath = subdir.join(filename);
-							self.state = BlockState::Complete;
-							runtime.logger.service_log("bootloader", &format!("Scan of {} found bootable at {:?}", self.name, entry.path()));
-							self.entries.push(BootEntry {
-								kernel : subpath,
-								initramfs : None,
-								append : None,
-							});
-							return;
-						}
-					}
-				}
-			}
-		}
-
-		runtime.logger.service_log("bootloader", &format!("Scan complete of {}, nothing found to boot", self.name));
-		self.state = BlockState::Complete;
-	}
-
 	fn event(&mut self, runtime : &mut Runtime, event : &ServiceEvent) -> bool {
 		match event {
 			ServiceEvent::ProcessExited(pid, status) => {
@@ -255,14 +637,14 @@ impl DeviceProbe
 							return false;
 						}
 
-						// Mount process has finished
+						// Mount process has finished; the matcher pipeline takes
+						// over from BlockState::Scanning (see Bootloader::event)
 						if !status.success() {
 							runtime.logger.service_log("bootloader", &format!("Mount failed for {}, return code = {}", self.name, status));
 							self.state = BlockState::Complete;
 						} else {
 							runtime.logger.service_log("bootloader", &format!("Mount completed for {}", self.name));
 							self.state = BlockState::Scanning;
-							self.scan(runtime);
 						}
 						return true;
 					},
@@ -282,16 +664,79 @@ impl DeviceProbe
 pub struct Bootloader
 {
 	checked : Vec<DeviceProbe>,
+	order : Vec<BootSelector>,
+	matchers : Vec<Box<dyn Matcher>>,
+	mounts : procfs::MountInfo,
+	recovery : Option<serial_boot::SerialBootHandle>,
 }
 
 impl Bootloader
 {
-	pub fn new() -> Self {
+	pub fn new(order : Vec<BootSelector>) -> Self {
 		return Self {
 			checked : Vec::new(),
+			order : order,
+			matchers : vec![
+				// BlsMatcher first: an ESP with loader/entries/*.conf alongside
+				// EFI/BOOT/BOOTX64.EFI (e.g. systemd-boot) should boot the real
+				// kernel entries, not the EFI-stub fallback kexec can't load.
+				Box::new(BlsMatcher),
+				Box::new(EfiRemovableMatcher),
+				Box::new(RawKernelMatcher),
+			],
+			mounts : procfs::MountInfo::new(),
+			recovery : None,
 			};
 	}
 
+	/// Append a matcher, tried after all built-in ones.
+	pub fn add_matcher(&mut self, matcher : Box<dyn Matcher>) {
+		self.matchers.push(matcher);
+	}
+
+	/// Fall back to XMODEM serial recovery, via the paired `SerialBoot`
+	/// service, once every configured boot source is exhausted with nothing
+	/// to boot. See `examples/bootloader.rs` for wiring the two together.
+	pub fn with_serial_recovery(mut self, handle : serial_boot::SerialBootHandle) -> Self {
+		self.recovery = Some(handle);
+		return self;
+	}
+
+	// Try each registered matcher in order against a mounted partition,
+	// stopping at the first one that successfully produces entries.
+	fn run_matchers(&mut self, index : usize, runtime : &mut Runtime) {
+		let mut matched = None;
+		let mut entries = Vec::new();
+
+		for (m_index, matcher) in self.matchers.iter_mut().enumerate() {
+			if !matcher.matches(&self.checked[index]) {
+				continue;
+			}
+
+			match matcher.process(&mut self.checked[index], runtime) {
+				Ok(found) => {
+					matched = Some(m_index);
+					entries = found;
+					break;
+				},
+				Err(e) => {
+					runtime.logger.service_log("bootloader", &format!("matcher #{} failed for {}: {:?}", m_index, self.checked[index].name, e));
+				},
+			}
+		}
+
+		let probe = &mut self.checked[index];
+		probe.matched = matched;
+		if let Some(m_index) = matched {
+			runtime.logger.service_log("bootloader", &format!("Scan of {} matched matcher #{}, found {} entries", probe.name, m_index, entries.len()));
+			verify_entries(&probe.point, &mut entries, runtime);
+			probe.entries = entries;
+		} else {
+			runtime.logger.service_log("bootloader", &format!("Scan complete of {}, nothing found to boot", probe.name));
+		}
+		probe.state = BlockState::Complete;
+	}
+
 	fn probe_partition(&mut self, runtime : &mut Runtime, name : String) -> bool {
 		for i in &self.checked {
 			// already exists
@@ -303,7 +748,7 @@ impl Bootloader
 		match DeviceProbe::new(&name) {
 			Ok(mut block) => {
 				runtime.logger.service_log("bootloader", &format!("found existing block device {} to probe", &block.name));
-				match block.mount(runtime) {
+				match block.mount(runtime, &self.mounts) {
 					Ok(_) => {
 						self.checked.push(block);
 					},
@@ -320,22 +765,213 @@ impl Bootloader
 		}
 	}
 
-	fn select_boot_entry(&self, order : &[BlockDeviceType]) -> Option<BootEntry> {
-		for t in order {
-			for i in &self.checked {
-				// already exists
-				match i.state {
-					BlockState::Complete => {
-						if i.devicetype == *t && i.entries.len() != 0{
-							return Some(i.entries[0].clone());
-						}
-					},
-					_ => {},
+	// Whether some probe that has not yet reached BlockState::Complete could
+	// still turn out to satisfy `selector`. Device type is known as soon as the
+	// probe is created, and uuid/label are too, since DeviceProbe::mount reads
+	// the superblock before the probe is kept.
+	fn selector_pending(&self, selector : &BootSelector) -> bool {
+		self.checked.iter().any(|i| {
+			if matches!(i.state, BlockState::Complete) {
+				return false;
+			}
+			match selector {
+				BootSelector::DeviceType(t) => i.devicetype == *t,
+				BootSelector::Uuid(uuid) => i.uuid.as_deref() == Some(uuid.as_str()),
+				BootSelector::Label(label) => i.label.as_deref() == Some(label.as_str()),
+			}
+		})
+	}
+
+	// Walk `order`, stopping at the first selector that either finds a
+	// verified entry or still has an outstanding probe that might satisfy it.
+	// The latter case defers selection entirely so a lower-priority device
+	// finishing first (e.g. USB mounting before Internal) never jumps the
+	// configured order.
+	fn select_boot_entry(&self, order : &[BootSelector]) -> Option<(usize, BootEntry)> {
+		for selector in order {
+			if self.selector_pending(selector) {
+				return None;
+			}
+
+			for (index, i) in self.checked.iter().enumerate() {
+				if !matches!(i.state, BlockState::Complete) || i.entries.is_empty() {
+					continue;
+				}
+
+				let matched = match selector {
+					BootSelector::DeviceType(t) => i.devicetype == *t,
+					BootSelector::Uuid(uuid) => i.uuid.as_deref() == Some(uuid.as_str()),
+					BootSelector::Label(label) => i.label.as_deref() == Some(label.as_str()),
+				};
+
+				if !matched {
+					continue;
+				}
+
+				// Entries that failed signature verification are kept around
+				// for logging but are never selected.
+				if let Some(entry) = i.entries.iter().find(|e| e.verified) {
+					return Some((index, entry.clone()));
 				}
 			}
 		}
 		return None;
 	}
+
+	// Drop the (now failed) entry so the next call to select_boot_entry
+	// advances to its next candidate.
+	fn invalidate_entry(&mut self, index : usize, id : &str) {
+		self.checked[index].entries.retain(|e| e.id != id);
+	}
+
+	// Concatenate the (possibly multiple) `initrd` files of a BLS entry into the
+	// single image kexec_file_load() accepts, per the Boot Loader Specification.
+	fn prepare_initrd(point : &Path, entry : &BootEntry) -> Result<Option<std::fs::File>> {
+		if entry.initramfs.is_empty() {
+			return Ok(None);
+		}
+
+		if entry.initramfs.len() == 1 {
+			return Ok(Some(std::fs::File::open(point.join(&entry.initramfs[0]))?));
+		}
+
+		let scratch_dir = PathBuf::from("/var/volatile/bootloader");
+		std::fs::create_dir_all(&scratch_dir)?;
+		let combined = scratch_dir.join(format!("{}.initrd", entry.id));
+
+		let mut out = std::fs::File::create(&combined)?;
+		for path in &entry.initramfs {
+			let mut input = std::fs::File::open(point.join(path))?;
+			std::io::copy(&mut input, &mut out)?;
+		}
+
+		return Ok(Some(std::fs::File::open(&combined)?));
+	}
+
+	// An embedded `ImageHeader` (unlike a detached ".sig") is prepended to the
+	// kernel file itself, so the raw file isn't a valid kexec image; strip it
+	// to a scratch copy before loading. A no-op for images with no header.
+	fn strip_embedded_header(point : &Path, entry : &BootEntry) -> Result<std::fs::File> {
+		let kernel_path = point.join(&entry.kernel);
+		let mut file = std::fs::File::open(&kernel_path)?;
+
+		let mut header = [0u8; IMAGE_HEADER_LEN];
+		if file.read_exact(&mut header).is_ok() && ImageHeader::parse(&header).is_some() {
+			let scratch_dir = PathBuf::from("/var/volatile/bootloader");
+			std::fs::create_dir_all(&scratch_dir)?;
+			let stripped = scratch_dir.join(format!("{}.kernel", entry.id));
+
+			let mut out = std::fs::File::create(&stripped)?;
+			std::io::copy(&mut file, &mut out)?; // file's cursor is already past the header
+			return Ok(std::fs::File::open(&stripped)?);
+		}
+
+		return Ok(std::fs::File::open(&kernel_path)?);
+	}
+
+	// Load the entry via kexec_file_load() and, on success, tear down the
+	// running system and jump into it. Only returns on failure, either to load
+	// the entry or to reboot into it.
+	//
+	// `point` is the partition mount point that `entry.kernel`/`initramfs` are
+	// relative to; pass "/" with an already-absolute `entry.kernel` to boot an
+	// entry that is not backed by a probed partition (e.g. SerialBoot).
+	pub(crate) fn kexec_boot_entry(point : &Path, entry : &BootEntry, runtime : &mut Runtime, mounts : &[PathBuf]) -> Result<()> {
+		let kernel = Self::strip_embedded_header(point, entry)?;
+		let initrd = Self::prepare_initrd(point, entry)?;
+		let cmdline = entry.append.clone().unwrap_or_default();
+
+		runtime.logger.service_log("bootloader", &format!("kexec loading entry {:?} ({:?})", entry.id, entry.kernel));
+		kexec::load(&kernel, initrd.as_ref(), &cmdline, 0)?;
+
+		runtime.logger.service_log("bootloader", "kexec load succeeded, tearing down for reboot");
+		runtime.logger.flush();
+
+		let _ = Command::new("sync").status();
+		for point in mounts {
+			let _ = Command::new("umount").arg(point).status();
+		}
+
+		kexec::reboot_to_kexec()?;
+		return Ok(());
+	}
+
+	// Try every candidate in the configured selector order, falling back to the
+	// next one whenever a boot fails so a single bad image does not brick boot.
+	// Once every candidate is exhausted, arm serial recovery as a last resort.
+	fn attempt_boot(&mut self, runtime : &mut Runtime, order : &[BootSelector]) {
+		let mounts : Vec<PathBuf> = self.checked.iter().map(|i| i.point.clone()).collect();
+
+		loop {
+			let (index, entry) = match self.select_boot_entry(order) {
+				Some(found) => found,
+				None => {
+					self.arm_recovery_if_exhausted(runtime);
+					return;
+				},
+			};
+
+			let point = self.checked[index].point.clone();
+			let name = self.checked[index].name.clone();
+			if let Err(e) = Self::kexec_boot_entry(&point, &entry, runtime, &mounts) {
+				runtime.logger.service_log("bootloader", &format!("Failed to kexec entry {:?} from {}: {:?}", entry.id, name, e));
+				self.invalidate_entry(index, &entry.id);
+				continue;
+			}
+
+			// Ok means reboot_to_kexec() returned without jumping into the
+			// loaded kernel; stop rather than load another entry over it
+			return;
+		}
+	}
+
+	fn all_probes_complete(&self) -> bool {
+		self.checked.iter().all(|i| matches!(i.state, BlockState::Complete))
+	}
+
+	// Called with select_boot_entry(order) already known to be None: arm the
+	// paired SerialBoot so a board with nothing else to boot falls back to
+	// XMODEM recovery instead of sitting idle.
+	fn arm_recovery_if_exhausted(&mut self, runtime : &mut Runtime) {
+		let recovery = match &self.recovery {
+			Some(recovery) => recovery,
+			None => return,
+		};
+
+		if !recovery.is_armed() && self.all_probes_complete() {
+			runtime.logger.service_log("bootloader", "no bootable entry found on any configured device, arming serial recovery");
+			recovery.arm();
+		}
+	}
+
+	// Kexec whatever image serial recovery has received so far, if any. Run
+	// through the same signature gate as probed entries first: serial access
+	// is exactly the kind of physical access a locked-down deployment's
+	// trusted key is meant to guard against, so recovery gets no free pass.
+	fn attempt_recovery_boot(&mut self, runtime : &mut Runtime) {
+		let recovery = match &self.recovery {
+			Some(recovery) => recovery.clone(),
+			None => return,
+		};
+
+		let mut entry = match recovery.boot_entry() {
+			Some(entry) => entry,
+			None => return,
+		};
+
+		verify_entries(Path::new("/"), std::slice::from_mut(&mut entry), runtime);
+		if !entry.verified {
+			runtime.logger.service_log("bootloader", "rejecting serial recovery image: signature verification failed");
+			recovery.reject();
+			return;
+		}
+
+		let mounts : Vec<PathBuf> = self.checked.iter().map(|i| i.point.clone()).collect();
+		if let Err(e) = Self::kexec_boot_entry(Path::new("/"), &entry, runtime, &mounts) {
+			runtime.logger.service_log("bootloader", &format!("Failed to kexec serial recovery image: {:?}", e));
+			recovery.reject();
+		}
+	}
 }
 
 impl Service for Bootloader
@@ -391,14 +1027,34 @@ impl Service for Bootloader
 
 	fn event(&mut self, runtime : &mut Runtime, event : ServiceEvent) -> bool {
 		let mut handled = false;
-		for i in &mut self.checked {
-			handled |= i.event(runtime, &event);
+		let mut to_scan = Vec::new();
+		for (index, i) in self.checked.iter_mut().enumerate() {
+			if i.event(runtime, &event) {
+				handled = true;
+				if matches!(i.state, BlockState::Scanning) {
+					to_scan.push(index);
+				}
+			}
+		}
+
+		if !to_scan.is_empty() {
+			// A mount just completed, the cached mountinfo view is now stale
+			self.mounts.refresh();
+		}
+
+		for index in to_scan {
+			self.run_matchers(index, runtime);
 		}
 
 		if handled {
+			let order = self.order.clone();
+			self.attempt_boot(runtime, &order);
+			self.attempt_recovery_boot(runtime);
 			return true;
 		}
 
+		self.attempt_recovery_boot(runtime);
+
 		// Monitor for new block devices
 		match event {
 			ServiceEvent::Device(dev) => {
@@ -0,0 +1,233 @@
+use std::cell::RefCell;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use super::super::*;
+use super::bootloader;
+use service::{Service, ServiceEvent, ServiceState};
+use runtime::Runtime;
+use crate::Result;
+
+const SOH : u8 = 0x01;
+const EOT : u8 = 0x04;
+const ACK : u8 = 0x06;
+const NAK : u8 = 0x15;
+const CRC_MODE : u8 = b'C';
+
+const DATA_SIZE : usize = 128;
+
+enum State
+{
+	Idle,
+	Received(PathBuf),
+	Failed,
+}
+
+struct Shared
+{
+	armed : bool,
+	result : Option<PathBuf>,
+	tty : Option<std::fs::File>,
+}
+
+/// Coordinates `SerialBoot` with `Bootloader` (and, for the shared tty, with
+/// `ConsoleService`) despite all being independent sibling `Service`s:
+/// `Bootloader` calls `arm` once every configured boot source is exhausted,
+/// `ConsoleService` reacts to `is_armed` by tearing down the login shell it
+/// is serving on that tty and handing the descriptor over via
+/// `relinquish_tty`, and `Bootloader` later polls `boot_entry` for the
+/// recovered image. Built once by the caller and cloned into both services
+/// (see `examples/bootloader.rs`).
+#[derive(Clone)]
+pub struct SerialBootHandle(Rc<RefCell<Shared>>);
+
+impl SerialBootHandle
+{
+	pub fn new() -> Self {
+		return Self(Rc::new(RefCell::new(Shared { armed : false, result : None, tty : None })));
+	}
+
+	/// Request that the paired `SerialBoot` take over its tty and wait for an
+	/// XMODEM transfer instead of serving a login shell.
+	pub fn arm(&self) {
+		self.0.borrow_mut().armed = true;
+	}
+
+	pub(crate) fn is_armed(&self) -> bool {
+		self.0.borrow().armed
+	}
+
+	/// Hand over the tty's already-open descriptor once its current owner has
+	/// relinquished it (e.g. `ConsoleService` killing the login shell it was
+	/// serving), so `SerialBoot` never has to open a second, competing fd.
+	pub fn relinquish_tty(&self, tty : std::fs::File) {
+		self.0.borrow_mut().tty = Some(tty);
+	}
+
+	fn take_tty(&self) -> Option<std::fs::File> {
+		self.0.borrow_mut().tty.take()
+	}
+
+	fn set_result(&self, path : Option<PathBuf>) {
+		self.0.borrow_mut().result = path;
+	}
+
+	/// The recovered image as a bootable entry, once a transfer has completed.
+	pub fn boot_entry(&self) -> Option<bootloader::BootEntry> {
+		self.0.borrow().result.as_ref().map(|path| bootloader::recovery_entry("serial-recovery", path.clone(), None))
+	}
+
+	/// Discard a received image that `Bootloader` couldn't use (failed
+	/// verification, failed to kexec) so `boot_entry` stops handing it back
+	/// every tick.
+	pub(crate) fn reject(&self) {
+		self.0.borrow_mut().result = None;
+	}
+}
+
+/// Field-recovery boot source: once armed via its `SerialBootHandle`, takes
+/// over a tty and streams a kernel image in via XMODEM/CRC instead of
+/// requiring a bootable block device. Shares the handle with `Bootloader` so
+/// a port can serve a login shell until the moment recovery mode is armed.
+pub struct SerialBoot
+{
+	tty : PathBuf,
+	handle : SerialBootHandle,
+	state : State,
+}
+
+impl SerialBoot
+{
+	pub fn new(tty : &str, handle : SerialBootHandle) -> Self {
+		return Self {
+			tty : PathBuf::from("/dev").join(tty),
+			handle : handle,
+			state : State::Idle,
+		};
+	}
+
+	fn receive(&self, mut port : std::fs::File, runtime : &mut Runtime) -> Result<PathBuf> {
+		let dest = PathBuf::from("/var/volatile/serial-boot.img");
+		let mut out = std::fs::File::create(&dest)?;
+
+		// Request 16-bit CRC mode; the sender replies with SOH once it sees 'C'
+		port.write_all(&[CRC_MODE])?;
+
+		let mut expected : u8 = 1;
+		loop {
+			let mut header = [0u8; 1];
+			if port.read(&mut header)? == 0 {
+				// no data within the tty's read timeout; re-send the mode
+				// request rather than NAK, since we haven't seen an SOH yet
+				// and NAK here would downgrade the transfer to checksum mode
+				port.write_all(&[CRC_MODE])?;
+				continue;
+			}
+
+			match header[0] {
+				EOT => {
+					port.write_all(&[ACK])?;
+					break;
+				},
+				SOH => {
+					let mut frame = [0u8; 2 + DATA_SIZE + 2];
+					port.read_exact(&mut frame)?;
+
+					let block = frame[0];
+					let block_check = frame[1];
+					let data = &frame[2..2 + DATA_SIZE];
+					let crc = ((frame[2 + DATA_SIZE] as u16) << 8) | (frame[3 + DATA_SIZE] as u16);
+
+					if block != !block_check || crc16(data) != crc {
+						runtime.logger.service_log("serial-boot", &format!("bad block {}, requesting retransmit", block));
+						port.write_all(&[NAK])?;
+						continue;
+					}
+
+					if block == expected {
+						out.write_all(data)?;
+						expected = expected.wrapping_add(1);
+					} else if block == expected.wrapping_sub(1) {
+						// sender didn't see our ACK for the previous block; ACK it
+						// again without rewriting, per the XMODEM retransmit rule
+					} else {
+						// neither the expected block nor a retransmit of the last
+						// one: a gap, which would silently corrupt the image
+						runtime.logger.service_log("serial-boot", &format!("block {} out of sequence (expected {}), requesting retransmit", block, expected));
+						port.write_all(&[NAK])?;
+						continue;
+					}
+					port.write_all(&[ACK])?;
+				},
+				_ => {
+					port.write_all(&[NAK])?;
+				},
+			}
+		}
+
+		runtime.logger.service_log("serial-boot", &format!("received image on {:?} at {:?}", self.tty, dest));
+		return Ok(dest);
+	}
+}
+
+// XMODEM uses CRC-16/XMODEM: poly 0x1021, init 0x0000, no reflection.
+fn crc16(data : &[u8]) -> u16 {
+	let mut crc : u16 = 0;
+	for &byte in data {
+		crc ^= (byte as u16) << 8;
+		for _ in 0..8 {
+			if crc & 0x8000 != 0 {
+				crc = (crc << 1) ^ 0x1021;
+			} else {
+				crc <<= 1;
+			}
+		}
+	}
+	return crc;
+}
+
+impl Service for SerialBoot
+{
+	fn setup(&mut self, _runtime : &mut Runtime) {}
+
+	fn state(&self) -> ServiceState {
+		match self.state {
+			State::Received(_) => ServiceState::Running,
+			_ => ServiceState::Inactive,
+		}
+	}
+
+	fn start(&mut self, _runtime : &mut Runtime) {}
+
+	fn stop(&mut self, _runtime : &mut Runtime) {}
+
+	// SerialBoot has no device/process of its own to watch; every event is
+	// just an opportunity to notice that `Bootloader` has armed recovery and,
+	// once `ConsoleService` has relinquished the tty, to start receiving.
+	fn event(&mut self, runtime : &mut Runtime, _event : ServiceEvent) -> bool {
+		if !matches!(self.state, State::Idle) || !self.handle.is_armed() {
+			return false;
+		}
+
+		let port = match self.handle.take_tty() {
+			Some(port) => port,
+			// armed, but the tty's current owner hasn't handed it off yet;
+			// try again next event rather than opening a competing fd
+			None => return false,
+		};
+
+		runtime.logger.service_log("serial-boot", &format!("armed, awaiting XMODEM transfer on {:?}", self.tty));
+		match self.receive(port, runtime) {
+			Ok(path) => {
+				self.state = State::Received(path.clone());
+				self.handle.set_result(Some(path));
+			},
+			Err(e) => {
+				runtime.logger.service_log("serial-boot", &format!("XMODEM receive failed: {:?}", e));
+				self.state = State::Failed;
+			},
+		}
+		return true;
+	}
+}
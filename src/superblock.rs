@@ -0,0 +1,148 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Filesystems this crate can recognize from their on-disk superblock,
+/// without shelling out to `blkid`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FsType
+{
+	Ext,
+	Fat,
+	Iso9660,
+}
+
+impl FsType
+{
+	/// The `-t` argument `mount` expects for this filesystem.
+	pub fn mount_type(&self) -> &'static str {
+		match self {
+			FsType::Ext => "ext4",
+			FsType::Fat => "vfat",
+			FsType::Iso9660 => "iso9660",
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct SuperblockInfo
+{
+	pub fstype : FsType,
+	pub uuid : Option<String>,
+	pub label : Option<String>,
+}
+
+const EXT_MAGIC_OFFSET : u64 = 1080;
+const EXT_MAGIC : u16 = 0xEF53;
+const EXT_UUID_OFFSET : u64 = 1128;
+const EXT_LABEL_OFFSET : u64 = 1144;
+const EXT_LABEL_LEN : usize = 16;
+
+const ISO9660_MAGIC_OFFSET : u64 = 0x8001;
+const ISO9660_MAGIC : &[u8; 5] = b"CD001";
+const ISO9660_LABEL_OFFSET : u64 = 0x8028;
+const ISO9660_LABEL_LEN : usize = 32;
+
+// (boot-signature offset, volume-serial offset, volume-label offset, fs-type string offset)
+const FAT32_LAYOUT : (u64, u64, u64, u64) = (66, 67, 71, 82);
+const FAT1X_LAYOUT : (u64, u64, u64, u64) = (38, 39, 43, 54);
+
+fn read_at(file : &mut File, offset : u64, buf : &mut [u8]) -> Option<()> {
+	file.seek(SeekFrom::Start(offset)).ok()?;
+	file.read_exact(buf).ok()?;
+	return Some(());
+}
+
+fn format_uuid(bytes : &[u8; 16]) -> String {
+	return format!(
+		"{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+		bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+		bytes[8], bytes[9], bytes[10], bytes[11], bytes[12], bytes[13], bytes[14], bytes[15]);
+}
+
+fn trim_trailing_str(bytes : &[u8]) -> Option<String> {
+	let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+	let text = String::from_utf8_lossy(&bytes[..end]).trim_end().to_owned();
+	if text.is_empty() {
+		return None;
+	}
+	return Some(text);
+}
+
+fn probe_ext(file : &mut File) -> Option<SuperblockInfo> {
+	let mut magic = [0u8; 2];
+	read_at(file, EXT_MAGIC_OFFSET, &mut magic)?;
+	if u16::from_le_bytes(magic) != EXT_MAGIC {
+		return None;
+	}
+
+	let mut uuid_bytes = [0u8; 16];
+	let uuid = read_at(file, EXT_UUID_OFFSET, &mut uuid_bytes).map(|_| format_uuid(&uuid_bytes));
+
+	let mut label_bytes = [0u8; EXT_LABEL_LEN];
+	let label = read_at(file, EXT_LABEL_OFFSET, &mut label_bytes).and_then(|_| trim_trailing_str(&label_bytes));
+
+	return Some(SuperblockInfo { fstype : FsType::Ext, uuid : uuid, label : label });
+}
+
+fn probe_iso9660(file : &mut File) -> Option<SuperblockInfo> {
+	let mut magic = [0u8; 5];
+	read_at(file, ISO9660_MAGIC_OFFSET, &mut magic)?;
+	if &magic != ISO9660_MAGIC {
+		return None;
+	}
+
+	let mut label_bytes = [0u8; ISO9660_LABEL_LEN];
+	let label = read_at(file, ISO9660_LABEL_OFFSET, &mut label_bytes).and_then(|_| trim_trailing_str(&label_bytes));
+
+	return Some(SuperblockInfo { fstype : FsType::Iso9660, uuid : None, label : label });
+}
+
+fn probe_fat(file : &mut File) -> Option<SuperblockInfo> {
+	// Check the boot sector signature, common to both FAT12/16 and FAT32
+	let mut boot_sector_sig = [0u8; 2];
+	read_at(file, 510, &mut boot_sector_sig)?;
+	if boot_sector_sig != [0x55, 0xAA] {
+		return None;
+	}
+
+	for &(boot_sig_off, serial_off, label_off, fstype_off) in &[FAT32_LAYOUT, FAT1X_LAYOUT] {
+		let mut boot_sig = [0u8; 1];
+		if read_at(file, boot_sig_off, &mut boot_sig).is_none() || (boot_sig[0] != 0x28 && boot_sig[0] != 0x29) {
+			continue;
+		}
+
+		let mut fstype = [0u8; 8];
+		if read_at(file, fstype_off, &mut fstype).is_none() || !fstype.starts_with(b"FAT") {
+			continue;
+		}
+
+		let mut serial = [0u8; 4];
+		let uuid = read_at(file, serial_off, &mut serial)
+			.map(|_| format!("{:02X}{:02X}-{:02X}{:02X}", serial[3], serial[2], serial[1], serial[0]));
+
+		let mut label_bytes = [0u8; 11];
+		let label = read_at(file, label_off, &mut label_bytes).and_then(|_| trim_trailing_str(&label_bytes));
+
+		return Some(SuperblockInfo { fstype : FsType::Fat, uuid : uuid, label : label });
+	}
+
+	return None;
+}
+
+/// Identify the filesystem on a block device node by reading its superblock
+/// directly, without depending on an external `blkid`.
+pub fn probe<P: AsRef<Path>>(device : P) -> Option<SuperblockInfo> {
+	let mut file = File::open(device).ok()?;
+
+	if let Some(info) = probe_ext(&mut file) {
+		return Some(info);
+	}
+	if let Some(info) = probe_iso9660(&mut file) {
+		return Some(info);
+	}
+	if let Some(info) = probe_fat(&mut file) {
+		return Some(info);
+	}
+	return None;
+}